@@ -0,0 +1,87 @@
+use winnow::prelude::*;
+use winnow::{
+  branch::alt,
+  character::{digit1, multispace0},
+  combinator::cut,
+  error::{ContextError, ParseError},
+  multi::{separated_foldl1, separated_foldr1},
+  sequence::{delimited, preceded},
+  IResult,
+};
+
+/// A small precedence-climbing expression grammar, built directly on
+/// `separated_foldl1`/`separated_foldr1` instead of collecting operands into
+/// a `Vec<i64>` with `separated_list0` and folding over it afterwards: the
+/// separator (the operator) is folded together with its operands as they're
+/// parsed. The left folds (`+`/`-`/`*`) need no intermediate allocation; the
+/// right fold for `^` buffers its operands before folding.
+///
+/// ```text
+/// expr   ::= term (('+' | '-') term)*      -- left-associative, folded
+/// term   ::= power ('*' power)*            -- left-associative, folded
+/// power  ::= factor ('^' factor)*          -- right-associative, folded
+/// factor ::= number | '(' expr ')'
+/// ```
+pub fn expr<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, i64, E> {
+  separated_foldl1(
+    term,
+    delimited(multispace0, alt(('+', '-')), multispace0),
+    |lhs, op, rhs| if op == '+' { lhs + rhs } else { lhs - rhs },
+  )(i)
+}
+
+/// `1*2*3` left-folds as `(1*2)*3`.
+fn term<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, i64, E> {
+  separated_foldl1(
+    power,
+    delimited(multispace0, '*', multispace0),
+    |lhs, _, rhs| lhs * rhs,
+  )(i)
+}
+
+/// `2^3^2` right-folds as `2^(3^2)`, unlike `+`/`-`/`*` above.
+fn power<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, i64, E> {
+  separated_foldr1(
+    factor,
+    delimited(multispace0, '^', multispace0),
+    |lhs, _, rhs| int_pow(lhs, rhs),
+  )(i)
+}
+
+/// `i64::pow` takes an unsigned exponent, but `rhs` here comes from a
+/// parenthesized sub-expression and can be negative (e.g. `2^(0-1)`);
+/// casting that to `u32` would wrap around to a huge number and panic on
+/// overflow. Integer exponentiation with a negative exponent is only exact
+/// for a base of `1` or `-1`, so fold anything else to `0` instead.
+fn int_pow(lhs: i64, rhs: i64) -> i64 {
+  match u32::try_from(rhs) {
+    Ok(rhs) => lhs.pow(rhs),
+    Err(_) => match lhs {
+      1 => 1,
+      -1 if rhs % 2 == 0 => 1,
+      -1 => -1,
+      _ => 0,
+    },
+  }
+}
+
+fn factor<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, i64, E> {
+  delimited(
+    multispace0,
+    alt((
+      digit1.map_res(str::parse),
+      preceded('(', cut(delimited(multispace0, expr, (multispace0, ')')))),
+    )),
+    multispace0,
+  )
+  .context("factor")
+  .parse_next(i)
+}
@@ -3,33 +3,39 @@ use std::str;
 use winnow::prelude::*;
 use winnow::{
   branch::alt,
-  bytes::one_of,
-  bytes::{escaped, tag, take_while},
-  character::{alphanumeric1 as alphanumeric, f64},
-  combinator::{cut, opt},
-  error::{ContextError, ParseError},
+  bytes::{escaped_transform, tag, take_while, take_while_m_n, take_till0, take_till1},
+  character::f64,
+  combinator::{cut, fail, iterator, opt, peg},
+  error::{ContextError, Error, ParseError},
   multi::separated_list0,
+  recovery::{resume_after, Diagnostics},
   sequence::{delimited, preceded, separated_pair, terminated},
+  stream::Stateful,
   IResult,
 };
+use std::cell::RefCell;
+use std::rc::Rc;
 
 /// the root element of a JSON parser is either an object or an array
+///
+/// This is written with `peg`'s operator-overloaded `Parser` wrapper instead
+/// of nested `delimited`/`alt` calls: `a * b` sequences two parsers keeping
+/// `b`'s output, `a - b` sequences them keeping `a`'s, and `|` is ordered
+/// choice, so `delimited(sp, alt((...)), opt(sp))` becomes
+/// `peg(sp) * alt - opt(peg(sp))`.
 pub fn root<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
   i: &'a str,
 ) -> IResult<&'a str, JsonValue, E> {
-  delimited(
-    sp,
-    alt((
-      hash.map(JsonValue::Object),
-      array.map(JsonValue::Array),
-      null.map(|_| JsonValue::Null),
-    )),
-    opt(sp),
-  )(i)
+  let value = peg(hash.map(JsonValue::Object))
+    | peg(array.map(JsonValue::Array))
+    | peg(null.map(|_| JsonValue::Null));
+
+  ((peg(sp) * value) - opt(peg(sp))).parse_next(i)
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub enum JsonValue {
+  #[default]
   Null,
   Str(String),
   Boolean(bool),
@@ -49,23 +55,69 @@ fn sp<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
   take_while(move |c| chars.contains(c))(i)
 }
 
-/// A nom parser has the following signature:
-/// `Input -> IResult<Input, Output, Error>`, with `IResult` defined as:
-/// `type IResult<I, O, E = (I, ErrorKind)> = Result<(I, O), Err<E>>;`
-///
-/// most of the times you can ignore the error type and use the default (but this
-/// examples shows custom error types later on!)
-///
-/// Here we use `&str` as input type, but nom parsers can be generic over
-/// the input type, and work directly with `&[u8]` or any other type that
-/// implements the required traits.
-///
-/// Finally, we can see here that the input and output type are both `&str`
-/// with the same lifetime tag. This means that the produced value is a subslice
-/// of the input data. and there is no allocation needed. This is the main idea
-/// behind nom's performance.
-fn parse_str<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
-  escaped(alphanumeric, '\\', one_of("\"n\\"))(i)
+/// Parses the interior of a JSON string (the part between the quotes),
+/// decoding escape sequences as it goes rather than just validating that
+/// they're well-formed. `escaped_transform` runs `take_till1` over runs of
+/// plain characters, and whenever it hits the control character `\`, hands
+/// off to `transform` to consume the escape and produce the characters it
+/// stands for, stitching everything into one owned `String`.
+fn parse_str<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, String, E> {
+  escaped_transform(
+    take_till1(|c| c == '"' || c == '\\'),
+    '\\',
+    alt((
+      '"'.value("\"".to_string()),
+      '\\'.value("\\".to_string()),
+      '/'.value("/".to_string()),
+      'b'.value("\u{8}".to_string()),
+      'f'.value("\u{c}".to_string()),
+      'n'.value("\n".to_string()),
+      'r'.value("\r".to_string()),
+      't'.value("\t".to_string()),
+      unicode_escape,
+    )),
+  )(i)
+}
+
+/// Parses a `uXXXX` escape (the leading `\` is already consumed by
+/// `escaped_transform`) into the `char`(s) it denotes, following RFC 8259.
+/// A codepoint in the high-surrogate range `D800..=DBFF` isn't a character
+/// on its own: it must be immediately followed by a `\u` low surrogate in
+/// `DC00..=DFFF`, and the pair is combined into a single scalar value. A
+/// surrogate that shows up without its other half is malformed JSON, so we
+/// `cut` there instead of letting `alt` try another branch.
+fn unicode_escape<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, String, E> {
+  let (i, _) = 'u'.parse_next(i)?;
+  let (i, high) = hex_u16(i)?;
+
+  if (0xD800..=0xDBFF).contains(&high) {
+    let (i, _) = cut(tag("\\u")).parse_next(i)?;
+    let (i, low) = cut(hex_u16).parse_next(i)?;
+    if !(0xDC00..=0xDFFF).contains(&low) {
+      return cut(fail).parse_next(i);
+    }
+
+    let c = 0x10000 + ((u32::from(high - 0xD800)) << 10) + u32::from(low - 0xDC00);
+    let c = char::from_u32(c).expect("a valid surrogate pair always decodes");
+    Ok((i, c.to_string()))
+  } else if (0xDC00..=0xDFFF).contains(&high) {
+    // a low surrogate with no preceding high surrogate
+    cut(fail).parse_next(i)
+  } else {
+    let c = char::from_u32(u32::from(high)).expect("a non-surrogate u16 is always a valid char");
+    Ok((i, c.to_string()))
+  }
+}
+
+/// Exactly 4 hex digits, parsed as the `u16` they spell out.
+fn hex_u16<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, u16, E> {
+  take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit())
+    .map_res(|s| u16::from_str_radix(s, 16))
+    .parse_next(i)
 }
 
 /// `tag(string)` generates a parser that recognizes the argument string.
@@ -74,27 +126,25 @@ fn parse_str<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str
 /// parser, and if that parser returns without an error, returns a given
 /// constant value.
 ///
-/// `alt` is another combinator that tries multiple parsers one by one, until
-/// one of them succeeds
+/// `|` on two `peg`-wrapped parsers is ordered choice: it returns the result
+/// of the first successful parser, or an error
 fn boolean<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, bool, E> {
   // This is a parser that returns `true` if it sees the string "true", and
   // an error otherwise
-  let parse_true = tag("true").value(true);
+  let parse_true = peg(tag("true").value(true));
 
   // This is a parser that returns `false` if it sees the string "false", and
   // an error otherwise
-  let parse_false = tag("false").value(false);
+  let parse_false = peg(tag("false").value(false));
 
-  // `alt` combines the two parsers. It returns the result of the first
-  // successful parser, or an error
-  alt((parse_true, parse_false))(input)
+  (parse_true | parse_false).parse_next(input)
 }
 
 fn null<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, (), E> {
   tag("null").value(()).parse_next(input)
 }
 
-/// this parser combines the previous `parse_str` parser, that recognizes the
+/// this parser combines the previous `parse_str` parser, that decodes the
 /// interior of a string, with a parse to recognize the double quote character,
 /// before the string (using `preceded`) and after the string (using `terminated`).
 ///
@@ -107,8 +157,8 @@ fn null<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, (), E> {
 /// error chain (to indicate which parser had an error)
 fn string<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
   i: &'a str,
-) -> IResult<&'a str, &'a str, E> {
-  preceded('\"', cut(terminated(parse_str, '\"')))
+) -> IResult<&'a str, String, E> {
+  (peg('\"') * cut(peg(parse_str) - '\"'))
     .context("string")
     .parse_next(i)
 }
@@ -120,55 +170,202 @@ fn string<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
 fn array<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
   i: &'a str,
 ) -> IResult<&'a str, Vec<JsonValue>, E> {
+  (peg('[') * cut(peg(separated_list0(peg(sp) * ',', json_value)) - (peg(sp) * ']')))
+    .context("array")
+    .parse_next(i)
+}
+
+/// Demonstrates the `iterator` combinator the note on `array` above points
+/// to: rather than always collecting elements into a `Vec` via
+/// `separated_list0`, `iterator` drives the repeated parse lazily, so a
+/// caller can fold over it directly. This sums the numeric elements of a
+/// top-level array without ever materializing a `Vec` for the elements it
+/// doesn't need to keep.
+pub fn sum_array_numbers<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, f64, E> {
+  let (i, _) = peg('[').parse_next(i)?;
+
+  let mut it = iterator(i, preceded(opt(peg(sp) * ','), json_value));
+  let sum = it
+    .filter_map(|v| match v {
+      JsonValue::Num(n) => Some(n),
+      _ => None,
+    })
+    .sum();
+  let (i, ()) = it.finish()?;
+
+  let (i, _) = (peg(sp) * ']').parse_next(i)?;
+  Ok((i, sum))
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, (String, JsonValue), E> {
+  separated_pair(preceded(sp, string), cut(preceded(sp, ':')), json_value)(i)
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, HashMap<String, JsonValue>, E> {
+  let entries =
+    separated_list0(peg(sp) * ',', key_value).map(|tuple_vec| tuple_vec.into_iter().collect());
+
+  (peg('{') * cut(peg(entries) - (peg(sp) * '}')))
+    .context("map")
+    .parse_next(i)
+}
+
+/// here, we apply the space parser before trying to parse a value
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, JsonValue, E> {
+  let value = peg(hash.map(JsonValue::Object))
+    | peg(array.map(JsonValue::Array))
+    | peg(string.map(JsonValue::Str))
+    | peg(f64.map(JsonValue::Num))
+    | peg(boolean.map(JsonValue::Boolean))
+    | peg(null.map(|_| JsonValue::Null));
+
+  (peg(sp) * value).parse_next(i)
+}
+
+// --- error-recovery mode --------------------------------------------------
+//
+// `root`/`json_value` above abort as soon as a `cut`'d parser fails. The
+// parsers below instead carry a shared diagnostics sink of every error
+// they've recovered from as `Stateful` state, so tools like an IDE or
+// linter can report all the problems in a document instead of just the
+// first one.
+
+/// Input for the recovering parsers: the remaining text, plus every
+/// diagnostic collected so far.
+type RInput<'a> = Stateful<&'a str, Diagnostics<Error<&'a str>>>;
+
+/// `Stateful` forwards `Stream` to its inner input, so most combinators
+/// (`tag`, `take_while`, `alt`, `cut`, ...) already run on `RInput`
+/// directly. Our own helpers above and `character::f64` are hard-coded to
+/// plain `&str`; this puts the input back together around a call to one of
+/// them.
+fn lift<'a, O>(
+  mut parser: impl FnMut(&'a str) -> IResult<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(RInput<'a>) -> IResult<RInput<'a>, O, Error<&'a str>> {
+  move |i: RInput<'a>| {
+    let Stateful { input, state } = i;
+    let (input, o) = parser(input)?;
+    Ok((Stateful { input, state }, o))
+  }
+}
+
+/// The recovery strategy: skip everything up to the next `,`, `]` or `}` so
+/// the enclosing `separated_list0` can resume at the next item.
+fn skip_to_delimiter<'a>(i: RInput<'a>) -> IResult<RInput<'a>, (), Error<&'a str>> {
+  lift(take_till0(|c| matches!(c, ',' | ']' | '}')).value(())).parse_next(i)
+}
+
+/// Recovery for the top-level call in [`parse_recoverable`]: if the whole
+/// document is unrecoverable (a hard failure that escapes every
+/// `resume_after` nested underneath, e.g. a missing closing `}`), skip to
+/// the end so the diagnostics collected so far aren't thrown away with it.
+fn skip_rest<'a>(i: RInput<'a>) -> IResult<RInput<'a>, (), Error<&'a str>> {
+  lift(take_till0(|_| false).value(())).parse_next(i)
+}
+
+fn json_value_recovering<'a>(i: RInput<'a>) -> IResult<RInput<'a>, JsonValue, Error<&'a str>> {
+  preceded(
+    lift(sp),
+    alt((
+      hash_recovering.map(JsonValue::Object),
+      array_recovering.map(JsonValue::Array),
+      lift(string).map(JsonValue::Str),
+      lift(f64).map(JsonValue::Num),
+      lift(boolean).map(JsonValue::Boolean),
+      lift(null).map(|_| JsonValue::Null),
+    )),
+  )(i)
+}
+
+fn array_recovering<'a>(i: RInput<'a>) -> IResult<RInput<'a>, Vec<JsonValue>, Error<&'a str>> {
   preceded(
     '[',
     cut(terminated(
-      separated_list0(preceded(sp, ','), json_value),
-      preceded(sp, ']'),
+      separated_list0(
+        preceded(lift(sp), ','),
+        resume_after(json_value_recovering, skip_to_delimiter),
+      ),
+      preceded(lift(sp), ']'),
     )),
   )
   .context("array")
   .parse_next(i)
 }
 
-fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
-  i: &'a str,
-) -> IResult<&'a str, (&'a str, JsonValue), E> {
-  separated_pair(preceded(sp, string), cut(preceded(sp, ':')), json_value)(i)
+fn key_value_recovering<'a>(
+  i: RInput<'a>,
+) -> IResult<RInput<'a>, (String, JsonValue), Error<&'a str>> {
+  separated_pair(
+    preceded(lift(sp), lift(string)),
+    cut(preceded(lift(sp), ':')),
+    json_value_recovering,
+  )(i)
 }
 
-fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
-  i: &'a str,
-) -> IResult<&'a str, HashMap<String, JsonValue>, E> {
+fn hash_recovering<'a>(
+  i: RInput<'a>,
+) -> IResult<RInput<'a>, HashMap<String, JsonValue>, Error<&'a str>> {
   preceded(
     '{',
     cut(terminated(
-      separated_list0(preceded(sp, ','), key_value).map(|tuple_vec| {
-        tuple_vec
-          .into_iter()
-          .map(|(k, v)| (String::from(k), v))
-          .collect()
-      }),
-      preceded(sp, '}'),
+      separated_list0(
+        preceded(lift(sp), ','),
+        resume_after(key_value_recovering, skip_to_delimiter),
+      )
+      .map(|tuple_vec| tuple_vec.into_iter().collect()),
+      preceded(lift(sp), '}'),
     )),
   )
   .context("map")
   .parse_next(i)
 }
 
-/// here, we apply the space parser before trying to parse a value
-fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
-  i: &'a str,
-) -> IResult<&'a str, JsonValue, E> {
-  preceded(
-    sp,
+fn root_recovering<'a>(i: RInput<'a>) -> IResult<RInput<'a>, JsonValue, Error<&'a str>> {
+  delimited(
+    lift(sp),
     alt((
-      hash.map(JsonValue::Object),
-      array.map(JsonValue::Array),
-      string.map(|s| JsonValue::Str(String::from(s))),
-      f64.map(JsonValue::Num),
-      boolean.map(JsonValue::Boolean),
-      null.map(|_| JsonValue::Null),
+      hash_recovering.map(JsonValue::Object),
+      array_recovering.map(JsonValue::Array),
+      lift(null).map(|_| JsonValue::Null),
     )),
+    opt(lift(sp)),
   )(i)
 }
+
+/// Parses `i` like `root`, but instead of giving up at the first malformed
+/// value, resynchronizes at the next `,`, `]` or `}` and keeps going. Returns
+/// the best-effort tree alongside every error recovered from along the way.
+/// Only an `Err::Failure` is recovered from, i.e. one raised inside a string,
+/// array or object after its opening `"`, `[` or `{`; a value that isn't
+/// recognized at all (e.g. `tru`) ends the enclosing list, which then fails
+/// on its missing closing `]` or `}`.
+///
+/// `root_recovering` itself is wrapped in `resume_after`, so a hard failure
+/// that escapes every nested recovery (e.g. a missing closing `}`) still
+/// lands here as `Ok` with the diagnostics collected so far instead of
+/// throwing them away with the error; `None` only happens if the top-level
+/// value couldn't even be recognized as an object/array/null, before any
+/// diagnostic could have been recorded.
+pub fn parse_recoverable(i: &str) -> (Option<JsonValue>, Vec<Error<&str>>) {
+  let input = RInput {
+    input: i,
+    state: Rc::new(RefCell::new(Vec::new())),
+  };
+  match resume_after(root_recovering, skip_rest).parse_next(input) {
+    Ok((rest, value)) => (
+      Some(value),
+      Rc::try_unwrap(rest.state)
+        .expect("no other handle to the diagnostics sink should remain")
+        .into_inner(),
+    ),
+    Err(_) => (None, Vec::new()),
+  }
+}
@@ -0,0 +1,75 @@
+use winnow::prelude::*;
+use winnow::{
+  branch::alt,
+  bytes::streaming::{escaped, one_of, tag, take_while},
+  character::streaming::alphanumeric1 as alphanumeric,
+  combinator::cut,
+  error::{ContextError, ParseError},
+  multi::separated_list0,
+  sequence::{delimited, preceded, terminated},
+  Err, IResult, Needed,
+};
+
+/// A streaming dual of the combinators in `parser.rs`, for the case where
+/// the caller is feeding bytes off a socket or file a chunk at a time
+/// instead of handing over the whole document up front.
+///
+/// Everything here is built from `bytes::streaming`/`character::streaming`
+/// rather than the `complete` variants `parser.rs` uses: when one of these
+/// reaches the end of the currently available slice while it could still
+/// keep matching, it returns `Err::Incomplete(Needed::Size(n))` instead of
+/// failing outright, so the caller knows to read more bytes and retry with
+/// the same parser rather than treating the input as malformed.
+fn sp<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+  let chars = " \t\r\n";
+  take_while(move |c| chars.contains(c))(i)
+}
+
+fn parse_str<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+  escaped(alphanumeric, '\\', one_of("\"n\\"))(i)
+}
+
+fn string<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+  preceded('\"', cut(terminated(parse_str, '\"')))
+    .context("string")
+    .parse_next(i)
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str, &'static str>>(
+  i: &'a str,
+) -> IResult<&'a str, Vec<&'a str>, E> {
+  preceded(
+    '[',
+    cut(terminated(
+      separated_list0(preceded(sp, ','), preceded(sp, string)),
+      preceded(sp, ']'),
+    )),
+  )
+  .context("array")
+  .parse_next(i)
+}
+
+/// Feeds `array` a growing prefix of `full`, one byte at a time, to show
+/// that a chunk ending mid-token asks for more input instead of failing.
+/// A real caller would keep its own growable buffer and append to it as
+/// more bytes arrive from the socket/file, re-running the parser from the
+/// start of the buffer each time, exactly as below.
+pub fn decode_incrementally<'a>(
+  full: &'a str,
+) -> Result<(&'a str, Vec<&'a str>), Err<winnow::error::Error<&'a str>>> {
+  for end in 1..=full.len() {
+    if !full.is_char_boundary(end) {
+      continue;
+    }
+    match array::<winnow::error::Error<&str>>(&full[..end]) {
+      Ok((rest, value)) => return Ok((rest, value)),
+      Err(Err::Incomplete(Needed::Size(_) | Needed::Unknown)) => continue,
+      Err(err @ (Err::Error(_) | Err::Failure(_))) => return Err(err),
+    }
+  }
+  // Reached the end of `full` while the parser was still only asking for
+  // more bytes: a real streaming caller would read more off the wire here.
+  Err(Err::Incomplete(Needed::Unknown))
+}
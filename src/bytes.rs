@@ -0,0 +1,290 @@
+//! Byte- and string-oriented combinators.
+//!
+//! The free functions here (`escaped_transform`, `take_while_m_n`,
+//! `take_till0`, `take_till1`) only make sense for a document that's been
+//! parsed all at once; [`complete`] and [`streaming`] below host the
+//! complete/incremental duals of `tag`/`take_while`/`one_of`/`escaped`.
+
+use crate::error::{ErrorKind, ParseError};
+use crate::prelude::*;
+use crate::{Err, IResult};
+
+/// Like [`escaped`](complete::escaped), but instead of only validating that
+/// escape sequences are well-formed and handing back the original borrowed
+/// slice, actually runs `transform` on each escape and stitches its output
+/// together with the unescaped spans in between into one owned `String`.
+///
+/// `normal` is run first and repeatedly to consume a run of plain
+/// characters; when what's left starts with `control_char`, `transform` is
+/// handed the remainder (with `control_char` itself already stripped) and
+/// is expected to consume and decode exactly one escape sequence.
+pub fn escaped_transform<'a, O1, O2, E, F, G>(
+  mut normal: F,
+  control_char: char,
+  mut transform: G,
+) -> impl FnMut(&'a str) -> IResult<&'a str, String, E>
+where
+  E: ParseError<&'a str>,
+  O1: AsRef<str>,
+  O2: AsRef<str>,
+  F: Parser<&'a str, O1, E>,
+  G: Parser<&'a str, O2, E>,
+{
+  move |input: &'a str| {
+    let mut acc = String::new();
+    let mut i = input;
+    loop {
+      match normal.parse_next(i) {
+        Ok((rest, o)) => {
+          acc.push_str(o.as_ref());
+          i = rest;
+        }
+        Err(Err::Error(_)) => {}
+        Err(e) => return Err(e),
+      }
+
+      match i.strip_prefix(control_char) {
+        Some(rest) => {
+          let (rest, o) = transform.parse_next(rest)?;
+          acc.push_str(o.as_ref());
+          i = rest;
+        }
+        None => return Ok((i, acc)),
+      }
+    }
+  }
+}
+
+/// Takes between `min` and `max` characters matching `cond`, inclusive.
+/// Errors if fewer than `min` characters match.
+pub fn take_while_m_n<'a, E: ParseError<&'a str>>(
+  min: usize,
+  max: usize,
+  mut cond: impl FnMut(char) -> bool,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+  move |i: &'a str| {
+    let mut count = 0;
+    for (idx, c) in i.char_indices() {
+      if count == max {
+        return Ok((&i[idx..], &i[..idx]));
+      }
+      if !cond(c) {
+        return if count >= min {
+          Ok((&i[idx..], &i[..idx]))
+        } else {
+          Err(Err::Error(E::from_error_kind(i, ErrorKind::TakeWhileMN)))
+        };
+      }
+      count += 1;
+    }
+    if count >= min {
+      Ok(("", i))
+    } else {
+      Err(Err::Error(E::from_error_kind(i, ErrorKind::TakeWhileMN)))
+    }
+  }
+}
+
+/// Takes characters until `cond` matches, possibly none.
+pub fn take_till0<'a, E: ParseError<&'a str>>(
+  mut cond: impl FnMut(char) -> bool,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+  move |i: &'a str| match i.find(|c| cond(c)) {
+    Some(idx) => Ok((&i[idx..], &i[..idx])),
+    None => Ok(("", i)),
+  }
+}
+
+/// Like [`take_till0`], but errors if it doesn't consume at least one
+/// character.
+pub fn take_till1<'a, E: ParseError<&'a str>>(
+  mut cond: impl FnMut(char) -> bool,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+  move |i: &'a str| match i.find(|c| cond(c)) {
+    Some(0) => Err(Err::Error(E::from_error_kind(i, ErrorKind::TakeTill1))),
+    Some(idx) => Ok((&i[idx..], &i[..idx])),
+    None if i.is_empty() => Err(Err::Error(E::from_error_kind(i, ErrorKind::TakeTill1))),
+    None => Ok(("", i)),
+  }
+}
+
+/// `tag`/`take_while`/`one_of`/`escaped`, for a document that's been parsed
+/// all at once: the input is everything there is, so failing to match by
+/// the end of it is a hard "no", not a request for more bytes.
+pub mod complete {
+  use crate::error::{ErrorKind, ParseError};
+  use crate::prelude::*;
+  use crate::{Err, IResult};
+
+  /// Recognizes `pattern` at the start of the input.
+  pub fn tag<'a, E: ParseError<&'a str>>(
+    pattern: &'a str,
+  ) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |i: &'a str| {
+      if i.starts_with(pattern) {
+        Ok((&i[pattern.len()..], &i[..pattern.len()]))
+      } else {
+        Err(Err::Error(E::from_error_kind(i, ErrorKind::Tag)))
+      }
+    }
+  }
+
+  /// Takes characters matching `cond` for as long as they do, possibly
+  /// none.
+  pub fn take_while<'a, E: ParseError<&'a str>>(
+    mut cond: impl FnMut(char) -> bool,
+  ) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |i: &'a str| match i.find(|c| !cond(c)) {
+      Some(idx) => Ok((&i[idx..], &i[..idx])),
+      None => Ok(("", i)),
+    }
+  }
+
+  /// Recognizes one character out of `chars`.
+  pub fn one_of<'a, E: ParseError<&'a str>>(
+    chars: &'static str,
+  ) -> impl FnMut(&'a str) -> IResult<&'a str, char, E> {
+    move |i: &'a str| match i.chars().next() {
+      Some(c) if chars.contains(c) => Ok((&i[c.len_utf8()..], c)),
+      _ => Err(Err::Error(E::from_error_kind(i, ErrorKind::OneOf))),
+    }
+  }
+
+  /// Recognizes a run of `normal` tokens interleaved with `control_char`
+  /// followed by one `escapable` token, and returns the whole matched span
+  /// unchanged (see
+  /// [`escaped_transform`](super::escaped_transform) to decode it instead).
+  pub fn escaped<'a, O, E, F, G>(
+    mut normal: F,
+    control_char: char,
+    mut escapable: G,
+  ) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E>
+  where
+    E: ParseError<&'a str>,
+    F: Parser<&'a str, &'a str, E>,
+    G: Parser<&'a str, O, E>,
+  {
+    move |input: &'a str| {
+      let mut i = input;
+      loop {
+        match normal.parse_next(i) {
+          Ok((rest, _)) => i = rest,
+          Err(Err::Error(_)) => {}
+          Err(e) => return Err(e),
+        }
+
+        match i.strip_prefix(control_char) {
+          Some(rest) => {
+            let (rest, _) = escapable.parse_next(rest)?;
+            i = rest;
+          }
+          None => {
+            let consumed = input.len() - i.len();
+            return Ok((i, &input[..consumed]));
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Streaming duals of the combinators in [`complete`]: when one of these
+/// reaches the end of the currently available slice while it could still
+/// keep matching, it returns `Err::Incomplete(Needed)` instead of failing
+/// outright, so the caller knows to read more bytes and retry with the same
+/// parser rather than treating the input as malformed.
+pub mod streaming {
+  use crate::error::{ErrorKind, ParseError};
+  use crate::prelude::*;
+  use crate::{Err, IResult, Needed};
+
+  /// Recognizes `pattern` at the start of the input. If the input is a
+  /// proper prefix of `pattern`, more bytes could still complete the match,
+  /// so this asks for the rest of `pattern` instead of failing.
+  pub fn tag<'a, E: ParseError<&'a str>>(
+    pattern: &'a str,
+  ) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |i: &'a str| {
+      if i.len() < pattern.len() {
+        return if pattern.starts_with(i) {
+          Err(Err::Incomplete(Needed::new(pattern.len() - i.len())))
+        } else {
+          Err(Err::Error(E::from_error_kind(i, ErrorKind::Tag)))
+        };
+      }
+      if i.starts_with(pattern) {
+        Ok((&i[pattern.len()..], &i[..pattern.len()]))
+      } else {
+        Err(Err::Error(E::from_error_kind(i, ErrorKind::Tag)))
+      }
+    }
+  }
+
+  /// Takes characters matching `cond` for as long as they do. A run that
+  /// matches all the way to the end of the input might still be extended by
+  /// the next chunk, so this asks for one more byte instead of stopping
+  /// there.
+  pub fn take_while<'a, E: ParseError<&'a str>>(
+    mut cond: impl FnMut(char) -> bool,
+  ) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |i: &'a str| match i.find(|c| !cond(c)) {
+      Some(idx) => Ok((&i[idx..], &i[..idx])),
+      None => Err(Err::Incomplete(Needed::new(1))),
+    }
+  }
+
+  /// Recognizes one character out of `chars`. An empty input might still
+  /// produce a match once more bytes arrive.
+  pub fn one_of<'a, E: ParseError<&'a str>>(
+    chars: &'static str,
+  ) -> impl FnMut(&'a str) -> IResult<&'a str, char, E> {
+    move |i: &'a str| match i.chars().next() {
+      Some(c) if chars.contains(c) => Ok((&i[c.len_utf8()..], c)),
+      Some(_) => Err(Err::Error(E::from_error_kind(i, ErrorKind::OneOf))),
+      None => Err(Err::Incomplete(Needed::new(1))),
+    }
+  }
+
+  /// Streaming dual of [`complete::escaped`](super::complete::escaped): a
+  /// chunk that ends mid-run of `normal`, or right after `control_char`,
+  /// asks for more instead of treating the input as malformed.
+  pub fn escaped<'a, O, E, F, G>(
+    mut normal: F,
+    control_char: char,
+    mut escapable: G,
+  ) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E>
+  where
+    E: ParseError<&'a str>,
+    F: Parser<&'a str, &'a str, E>,
+    G: Parser<&'a str, O, E>,
+  {
+    move |input: &'a str| {
+      let mut i = input;
+      loop {
+        match normal.parse_next(i) {
+          Ok((rest, _)) => i = rest,
+          Err(Err::Error(_)) => {}
+          Err(e) => return Err(e),
+        }
+
+        if i.is_empty() {
+          return Err(Err::Incomplete(Needed::new(1)));
+        }
+
+        match i.strip_prefix(control_char) {
+          Some(rest) => {
+            if rest.is_empty() {
+              return Err(Err::Incomplete(Needed::new(1)));
+            }
+            let (rest, _) = escapable.parse_next(rest)?;
+            i = rest;
+          }
+          None => {
+            let consumed = input.len() - i.len();
+            return Ok((i, &input[..consumed]));
+          }
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,99 @@
+//! Character-oriented combinators.
+//!
+//! This module currently only hosts the handful of parsers the examples
+//! pull in directly (`char`, `digit1`, `multispace0`, `f64`) plus, in
+//! [`streaming`], a streaming `alphanumeric1`; the rest of `winnow::character` lives alongside
+//! them elsewhere in the crate.
+
+use crate::error::{ErrorKind, ParseError};
+use crate::{Err, IResult};
+
+/// Recognizes one specific character.
+pub fn char<'a, E: ParseError<&'a str>>(
+  c: char,
+) -> impl FnMut(&'a str) -> IResult<&'a str, char, E> {
+  move |i: &'a str| match i.chars().next() {
+    Some(found) if found == c => Ok((&i[found.len_utf8()..], found)),
+    _ => Err(Err::Error(E::from_error_kind(i, ErrorKind::Char))),
+  }
+}
+
+/// Recognizes one or more ASCII decimal digits.
+pub fn digit1<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+  match i.find(|c: char| !c.is_ascii_digit()) {
+    Some(0) => Err(Err::Error(E::from_error_kind(i, ErrorKind::Digit))),
+    Some(idx) => Ok((&i[idx..], &i[..idx])),
+    None if i.is_empty() => Err(Err::Error(E::from_error_kind(i, ErrorKind::Digit))),
+    None => Ok(("", i)),
+  }
+}
+
+/// Recognizes zero or more spaces, tabs, carriage returns and line feeds.
+pub fn multispace0<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+  match i.find(|c: char| !matches!(c, ' ' | '\t' | '\r' | '\n')) {
+    Some(idx) => Ok((&i[idx..], &i[..idx])),
+    None => Ok(("", i)),
+  }
+}
+
+/// Recognizes a floating point number (`[+-]?\d+(\.\d*)?([eE][+-]?\d+)?`) and
+/// parses it.
+pub fn f64<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, f64, E> {
+  let bytes = i.as_bytes();
+  let mut end = 0;
+
+  if matches!(bytes.get(end), Some(b'-') | Some(b'+')) {
+    end += 1;
+  }
+  let digits_start = end;
+  while matches!(bytes.get(end), Some(b'0'..=b'9')) {
+    end += 1;
+  }
+  if end == digits_start {
+    return Err(Err::Error(E::from_error_kind(i, ErrorKind::Float)));
+  }
+
+  if bytes.get(end) == Some(&b'.') {
+    end += 1;
+    while matches!(bytes.get(end), Some(b'0'..=b'9')) {
+      end += 1;
+    }
+  }
+
+  if matches!(bytes.get(end), Some(b'e') | Some(b'E')) {
+    let mut exp_end = end + 1;
+    if matches!(bytes.get(exp_end), Some(b'-') | Some(b'+')) {
+      exp_end += 1;
+    }
+    let exp_digits_start = exp_end;
+    while matches!(bytes.get(exp_end), Some(b'0'..=b'9')) {
+      exp_end += 1;
+    }
+    if exp_end > exp_digits_start {
+      end = exp_end;
+    }
+  }
+
+  i[..end]
+    .parse()
+    .map(|n| (&i[end..], n))
+    .map_err(|_| Err::Error(E::from_error_kind(i, ErrorKind::Float)))
+}
+
+/// Streaming character parsers: an input that matches all the way to the
+/// end asks for more instead of stopping there, since the next chunk might
+/// extend the match.
+pub mod streaming {
+  use crate::error::{ErrorKind, ParseError};
+  use crate::{Err, IResult, Needed};
+
+  /// Recognizes one or more ASCII alphanumeric characters.
+  pub fn alphanumeric1<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+    match i.find(|c: char| !c.is_ascii_alphanumeric()) {
+      Some(0) => Err(Err::Error(E::from_error_kind(i, ErrorKind::AlphaNumeric))),
+      Some(idx) => Ok((&i[idx..], &i[..idx])),
+      None if i.is_empty() => Err(Err::Error(E::from_error_kind(i, ErrorKind::AlphaNumeric))),
+      None => Err(Err::Incomplete(Needed::new(1))),
+    }
+  }
+}
@@ -0,0 +1,168 @@
+//! Combinators that don't belong to a more specific module.
+
+use std::ops::{BitOr, Mul, Sub};
+
+use crate::branch::alt;
+use crate::error::{ErrorKind, ParseError};
+use crate::multi::many0;
+use crate::prelude::*;
+use crate::sequence::{preceded, terminated};
+use crate::{Err, IResult};
+
+/// A parser that always fails, without consuming any input. Useful as the
+/// last branch of an `alt`, or to turn a runtime check into a `cut`-able
+/// error from inside a combinator body.
+pub fn fail<I: Clone, O, E: ParseError<I>>(input: I) -> IResult<I, O, E> {
+  Err(Err::Error(E::from_error_kind(input, ErrorKind::Fail)))
+}
+
+/// A `Parser` wrapped so a grammar can be written with `std::ops` instead of
+/// nested `preceded`/`terminated`/`alt` calls: `peg(a) * b` sequences two
+/// parsers keeping `b`'s output (`preceded`), `peg(a) - b` sequences them
+/// keeping `a`'s (`terminated`), `peg(a) | peg(b)` is ordered choice
+/// (`alt`), and `.repeat()` applies the wrapped parser zero or more times,
+/// collecting into a `Vec` (`many0`).
+///
+/// The inner parser is boxed so every `Peg` has the same concrete type
+/// regardless of what it was built from - otherwise `a * b - c` would need
+/// `Mul`/`Sub` to return a different, unnameable type at every step.
+pub struct Peg<'p, I, O, E>(Box<dyn FnMut(I) -> IResult<I, O, E> + 'p>);
+
+/// Wraps `parser` so it can be combined with `*`, `-`, `|` and `.repeat()`.
+/// See [`Peg`].
+pub fn peg<'p, I, O, E>(mut parser: impl Parser<I, O, E> + 'p) -> Peg<'p, I, O, E> {
+  Peg(Box::new(move |i| parser.parse_next(i)))
+}
+
+impl<'p, I, O, E> Parser<I, O, E> for Peg<'p, I, O, E> {
+  fn parse_next(&mut self, input: I) -> IResult<I, O, E> {
+    (self.0)(input)
+  }
+}
+
+impl<'p, I, O, E> Peg<'p, I, O, E>
+where
+  I: Clone + 'p,
+  O: 'p,
+  E: ParseError<I> + 'p,
+{
+  /// Applies the wrapped parser zero or more times, collecting the results
+  /// into a `Vec` (`many0`).
+  pub fn repeat(self) -> Peg<'p, I, Vec<O>, E> {
+    peg(many0(self))
+  }
+}
+
+impl<'p, I, O1, O2, E, Q> Mul<Q> for Peg<'p, I, O1, E>
+where
+  I: Clone + 'p,
+  O1: 'p,
+  E: ParseError<I> + 'p,
+  Q: Parser<I, O2, E> + 'p,
+{
+  type Output = Peg<'p, I, O2, E>;
+
+  /// Sequences two parsers, keeping the right one's output (`preceded`).
+  fn mul(self, rhs: Q) -> Self::Output {
+    peg(preceded(self, rhs))
+  }
+}
+
+impl<'p, I, O1, O2, E, Q> Sub<Q> for Peg<'p, I, O1, E>
+where
+  I: Clone + 'p,
+  O1: 'p,
+  E: ParseError<I> + 'p,
+  Q: Parser<I, O2, E> + 'p,
+{
+  type Output = Peg<'p, I, O1, E>;
+
+  /// Sequences two parsers, keeping the left one's output (`terminated`).
+  fn sub(self, rhs: Q) -> Self::Output {
+    peg(terminated(self, rhs))
+  }
+}
+
+impl<'p, I, O, E> BitOr for Peg<'p, I, O, E>
+where
+  I: Clone + 'p,
+  O: 'p,
+  E: ParseError<I> + 'p,
+{
+  type Output = Peg<'p, I, O, E>;
+
+  /// Ordered choice: the result of the first parser that succeeds, or an
+  /// error if neither does (`alt`).
+  fn bitor(self, rhs: Self) -> Self::Output {
+    peg(alt((self, rhs)))
+  }
+}
+
+/// Drives `parser` lazily over `input`, one `O` at a time, instead of
+/// collecting every result into a `Vec` the way `separated_list0`/`many0`
+/// do. Useful when a caller wants to fold over the results directly (a
+/// running sum, the first match, ...) without paying for the intermediate
+/// allocation.
+///
+/// Iterate it with the standard [`Iterator`] methods (`for`, `.sum()`,
+/// `.filter_map()`, ...), then call [`finish`](ParserIterator::finish) to
+/// get back the input right after the last successful parse - `parser` is
+/// run one extra time internally to know when to stop, and that attempt's
+/// `Err::Error` is where iteration ends, so `finish` is where that gets
+/// turned back into `Ok`/`Err` for the caller.
+pub fn iterator<I, O, E, F>(input: I, parser: F) -> ParserIterator<I, O, E, F>
+where
+  I: Clone,
+  F: Parser<I, O, E>,
+{
+  ParserIterator {
+    parser,
+    input,
+    state: Ok(()),
+  }
+}
+
+/// See [`iterator`].
+pub struct ParserIterator<I, O, E, F> {
+  parser: F,
+  input: I,
+  state: Result<(), Err<E>>,
+}
+
+impl<I, O, E, F> ParserIterator<I, O, E, F>
+where
+  I: Clone,
+  F: Parser<I, O, E>,
+{
+  /// Returns the input right after the last successful parse. If iteration
+  /// stopped because of an `Err::Failure`/`Err::Incomplete` (rather than
+  /// just running out of matches), that error is returned instead.
+  pub fn finish(self) -> IResult<I, (), E> {
+    self.state.map(|()| (self.input, ()))
+  }
+}
+
+impl<'i, I, O, E, F> Iterator for &'i mut ParserIterator<I, O, E, F>
+where
+  I: Clone,
+  F: Parser<I, O, E>,
+{
+  type Item = O;
+
+  fn next(&mut self) -> Option<O> {
+    if self.state.is_err() {
+      return None;
+    }
+    match self.parser.parse_next(self.input.clone()) {
+      Ok((rest, o)) => {
+        self.input = rest;
+        Some(o)
+      }
+      Err(Err::Error(_)) => None,
+      Err(e) => {
+        self.state = Err(e);
+        None
+      }
+    }
+  }
+}
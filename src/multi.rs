@@ -0,0 +1,78 @@
+//! Combinators that apply a parser repeatedly.
+
+use crate::error::ParseError;
+use crate::prelude::*;
+use crate::{Err, IResult};
+
+/// Parses `operand (separator operand)*` and left-folds the results as they
+/// come in: `fold(fold(fold(o0, s0, o1), s1, o2), s2, o3)`. Unlike
+/// `separated_list0(separator, operand)` followed by a `fold` over the
+/// resulting `Vec`, this never allocates — `acc` is the only state carried
+/// between iterations.
+pub fn separated_foldl1<I, O, OP, E, P, S, Op>(
+  mut operand: P,
+  mut sep: S,
+  mut fold: Op,
+) -> impl FnMut(I) -> IResult<I, O, E>
+where
+  I: Clone,
+  P: Parser<I, O, E>,
+  S: Parser<I, OP, E>,
+  Op: FnMut(O, OP, O) -> O,
+  E: ParseError<I>,
+{
+  move |i: I| {
+    let (mut i, mut acc) = operand.parse_next(i)?;
+    loop {
+      match sep.parse_next(i.clone()) {
+        Ok((rest, op)) => {
+          let (rest, rhs) = operand.parse_next(rest)?;
+          acc = fold(acc, op, rhs);
+          i = rest;
+        }
+        Err(Err::Error(_)) => return Ok((i, acc)),
+        Err(e) => return Err(e),
+      }
+    }
+  }
+}
+
+/// Like [`separated_foldl1`], but right-associative:
+/// `fold(o0, s0, fold(o1, s1, fold(o2, s2, o3)))`. A right fold can't know
+/// its starting point until every operand has been seen, so unlike
+/// `separated_foldl1` this does buffer the parsed operands/separators
+/// before folding from the last one back to the first.
+pub fn separated_foldr1<I, O, OP, E, P, S, Op>(
+  mut operand: P,
+  mut sep: S,
+  mut fold: Op,
+) -> impl FnMut(I) -> IResult<I, O, E>
+where
+  I: Clone,
+  P: Parser<I, O, E>,
+  S: Parser<I, OP, E>,
+  Op: FnMut(O, OP, O) -> O,
+  E: ParseError<I>,
+{
+  move |i: I| {
+    let (mut i, mut last) = operand.parse_next(i)?;
+    let mut pairs = Vec::new();
+    loop {
+      match sep.parse_next(i.clone()) {
+        Ok((rest, op)) => {
+          let (rest, rhs) = operand.parse_next(rest)?;
+          pairs.push((std::mem::replace(&mut last, rhs), op));
+          i = rest;
+        }
+        Err(Err::Error(_)) => break,
+        Err(e) => return Err(e),
+      }
+    }
+
+    let mut acc = last;
+    for (lhs, op) in pairs.into_iter().rev() {
+      acc = fold(lhs, op, acc);
+    }
+    Ok((i, acc))
+  }
+}
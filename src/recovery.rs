@@ -0,0 +1,81 @@
+//! Error-recovery combinators for parsers that carry a
+//! [`Stateful`](crate::stream::Stateful)`<I, Diagnostics<E>>` diagnostics
+//! sink, letting a grammar resynchronize after a `Failure` instead of
+//! aborting the whole parse.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::prelude::*;
+use crate::stream::Stateful;
+use crate::{Err, IResult};
+
+/// The diagnostics sink threaded through `Stateful`'s `state`. This has to
+/// be shared (`Rc<RefCell<_>>`), not a plain `Vec<E>`: combinators like
+/// `separated_list0` clone the `Stateful` input to try each element, and if
+/// a later element hard-fails, the clone carrying that element's own
+/// `resume_after` diagnostics is gone along with the `Err` - only a plain
+/// `Vec<E>` living in that lost clone would be thrown away with it. Sharing
+/// the backing `Vec` means every clone of `state` still points at the same
+/// diagnostics, so nothing recorded before the failure is lost.
+pub type Diagnostics<E> = Rc<RefCell<Vec<E>>>;
+
+/// Runs `parser`. If it succeeds, its result is passed through unchanged.
+/// If it returns `Err::Failure(e)`, `e` is pushed onto the [`Diagnostics`]
+/// carried in the input's state, `recovery` is run to resynchronize
+/// (typically by skipping to the next known-good token), and `O::default()`
+/// is substituted for the missing value so the caller (a `separated_list0`
+/// inside `array`/`hash`, say) can keep going instead of propagating the
+/// failure.
+///
+/// Any other error (`Err::Error`, `Err::Incomplete`) is not recoverable
+/// here and is passed through as-is.
+pub fn resume_after<I, O, E, F, R>(
+  mut parser: F,
+  mut recovery: R,
+) -> impl FnMut(Stateful<I, Diagnostics<E>>) -> IResult<Stateful<I, Diagnostics<E>>, O, E>
+where
+  I: Clone,
+  O: Default,
+  F: Parser<Stateful<I, Diagnostics<E>>, O, E>,
+  R: Parser<Stateful<I, Diagnostics<E>>, (), E>,
+{
+  move |input: Stateful<I, Diagnostics<E>>| match parser.parse_next(input.clone()) {
+    Ok(ok) => Ok(ok),
+    Err(Err::Failure(e)) => {
+      // `input.state` is the same `Rc` the failed clone of `parser` was
+      // mutating, so whatever it recorded before hitting the failure is
+      // already here even though the failure itself discarded its copy of
+      // the `Stateful`.
+      input.state.borrow_mut().push(e);
+      let (rest, ()) = recovery.parse_next(input)?;
+      Ok((rest, O::default()))
+    }
+    Err(e) => Err(e),
+  }
+}
+
+/// Like [`resume_after`], but re-runs `parser` itself (rather than
+/// substituting a default) once `recovery` has resynchronized the input.
+/// Useful when `recovery` skips over exactly the malformed span and what
+/// follows is expected to parse cleanly as another `O`, e.g. a recovery
+/// that skips a single bad token inside a run of otherwise-valid ones.
+pub fn retry_after<I, O, E, F, R>(
+  mut parser: F,
+  mut recovery: R,
+) -> impl FnMut(Stateful<I, Diagnostics<E>>) -> IResult<Stateful<I, Diagnostics<E>>, O, E>
+where
+  I: Clone,
+  F: Parser<Stateful<I, Diagnostics<E>>, O, E>,
+  R: Parser<Stateful<I, Diagnostics<E>>, (), E>,
+{
+  move |input: Stateful<I, Diagnostics<E>>| match parser.parse_next(input.clone()) {
+    Ok(ok) => Ok(ok),
+    Err(Err::Failure(e)) => {
+      input.state.borrow_mut().push(e);
+      let (rest, ()) = recovery.parse_next(input)?;
+      parser.parse_next(rest)
+    }
+    Err(e) => Err(e),
+  }
+}
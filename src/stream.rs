@@ -0,0 +1,120 @@
+//! Input wrappers.
+
+use crate::error::ParseError;
+use crate::IResult;
+
+/// Wraps an underlying input `I` together with arbitrary user state `S`
+/// (a string interner, a symbol table, a nesting-depth counter, a
+/// diagnostics sink, ...) that parsers can read and mutate as they go.
+///
+/// `Deref`/`DerefMut` to `I` are provided for convenience (so e.g. `.len()`
+/// or other inherent methods on `I` are one dot away), but they are *not*
+/// what lets combinators run on `Stateful` directly: `tag`, `take_while`,
+/// `one_of`, `delimited`, `fold_many0`, ... are all generic over `Stream`,
+/// and `Deref` doesn't satisfy a trait bound. `Stateful` forwards `Stream`
+/// to `I` instead, threading `state` through every token/slice it hands
+/// back so it survives unchanged across the call; only parsers that
+/// actually want to touch `S` need to know it's there, via [`state`] and
+/// [`modify_state`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stateful<I, S> {
+  pub input: I,
+  pub state: S,
+}
+
+impl<I, S> std::ops::Deref for Stateful<I, S> {
+  type Target = I;
+
+  fn deref(&self) -> &I {
+    &self.input
+  }
+}
+
+impl<I, S> std::ops::DerefMut for Stateful<I, S> {
+  fn deref_mut(&mut self) -> &mut I {
+    &mut self.input
+  }
+}
+
+impl<I: crate::stream::Stream, S: Clone> crate::stream::Stream for Stateful<I, S> {
+  type Token = I::Token;
+  type Slice = I::Slice;
+  type IterOffsets = I::IterOffsets;
+  type Checkpoint = Stateful<I::Checkpoint, S>;
+
+  fn iter_offsets(&self) -> Self::IterOffsets {
+    self.input.iter_offsets()
+  }
+
+  fn eof_offset(&self) -> usize {
+    self.input.eof_offset()
+  }
+
+  fn next_token(&self) -> Option<(Self, Self::Token)> {
+    let (input, token) = self.input.next_token()?;
+    Some((
+      Stateful {
+        input,
+        state: self.state.clone(),
+      },
+      token,
+    ))
+  }
+
+  fn offset_for<P>(&self, predicate: P) -> Option<usize>
+  where
+    P: Fn(Self::Token) -> bool,
+  {
+    self.input.offset_for(predicate)
+  }
+
+  fn offset_at(&self, tokens: usize) -> Result<usize, crate::Needed> {
+    self.input.offset_at(tokens)
+  }
+
+  fn next_slice(&self, offset: usize) -> (Self, Self::Slice) {
+    let (input, slice) = self.input.next_slice(offset);
+    (
+      Stateful {
+        input,
+        state: self.state.clone(),
+      },
+      slice,
+    )
+  }
+
+  fn checkpoint(&self) -> Self::Checkpoint {
+    Stateful {
+      input: self.input.checkpoint(),
+      state: self.state.clone(),
+    }
+  }
+
+  fn reset(&mut self, checkpoint: &Self::Checkpoint) {
+    self.input.reset(&checkpoint.input);
+    self.state = checkpoint.state.clone();
+  }
+}
+
+/// A parser that reads a clone of the current state without consuming any
+/// input.
+pub fn state<I, S: Clone, E: ParseError<Stateful<I, S>>>(
+  input: Stateful<I, S>,
+) -> IResult<Stateful<I, S>, S, E> {
+  let s = input.state.clone();
+  Ok((input, s))
+}
+
+/// A parser that runs `f` against the current state in place, without
+/// consuming any input. This is how `atom`/`list`-style parsers resolve the
+/// old `&mut ()` tomb workaround: instead of threading a mutable borrow
+/// through every combinator's signature, they carry `S` inside the input
+/// and reach for it with `modify_state` exactly where they need it.
+pub fn modify_state<I, S, E: ParseError<Stateful<I, S>>>(
+  mut f: impl FnMut(&mut S),
+) -> impl FnMut(Stateful<I, S>) -> IResult<Stateful<I, S>, (), E> {
+  move |mut input: Stateful<I, S>| {
+    f(&mut input.state);
+    Ok((input, ()))
+  }
+}
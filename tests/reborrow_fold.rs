@@ -3,27 +3,40 @@
 
 use std::str;
 
-use nom::bytes::is_not;
-use nom::character::char;
-use nom::multi::fold_many0;
-use nom::prelude::*;
-use nom::sequence::delimited;
-use nom::IResult;
+use winnow::bytes::is_not;
+use winnow::character::char;
+use winnow::multi::fold_many0;
+use winnow::prelude::*;
+use winnow::sequence::delimited;
+use winnow::stream::{modify_state, Stateful};
+use winnow::IResult;
 
-fn atom<'a>(_tomb: &'a mut ()) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], String> {
-  move |input| {
-    is_not(" \t\r\n")
-      .map_res(str::from_utf8)
-      .map(ToString::to_string)
-      .parse(input)
-  }
+// Threading a `&mut ()` tomb through by hand used to be the only way to get
+// a lifetime that let `atom` borrow something mutably; `Stateful` gives us a
+// real place to put that state instead. `S` here stands in for whatever a
+// caller wants to carry along (a string interner, a nesting-depth counter,
+// a diagnostics sink, ...); `atom` below actually reaches into
+// `input.state` through `modify_state` rather than just carrying `S` along
+// unused.
+type Input<'a, S> = Stateful<&'a [u8], S>;
+
+// Demonstrates a string interner: every atom `list` parses is recorded into
+// the caller-supplied `S` as it's found, instead of only being handed back
+// in the return value.
+fn atom<S: Extend<String>>(input: Input<'_, S>) -> IResult<Input<'_, S>, String> {
+  let (input, word) = is_not(" \t\r\n")
+    .map_res(str::from_utf8)
+    .map(ToString::to_string)
+    .parse_next(input)?;
+  let (input, ()) = modify_state(|interned: &mut S| interned.extend(std::iter::once(word.clone())))
+    .parse_next(input)?;
+  Ok((input, word))
 }
 
-// FIXME: should we support the use case of borrowing data mutably in a parser?
-fn list<'a>(i: &'a [u8], tomb: &'a mut ()) -> IResult<&'a [u8], String> {
+fn list<S: Extend<String>>(i: Input<'_, S>) -> IResult<Input<'_, S>, String> {
   delimited(
     char('('),
-    fold_many0(atom(tomb), String::new, |acc: String, next: String| {
+    fold_many0(atom, String::new, |acc: String, next: String| {
       acc + next.as_str()
     }),
     char(')'),